@@ -0,0 +1,47 @@
+use std::io::{Read, Write};
+
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+
+use crate::error::PicstegError;
+
+// Compresses `payload` with DEFLATE so more secret fits in the same cover image.
+pub fn compress(payload: &[u8]) -> Result<Vec<u8>, PicstegError> {
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+
+    encoder.write_all(payload).map_err(PicstegError::Compress)?;
+    encoder.finish().map_err(PicstegError::Compress)
+}
+
+// Reverses compress(): inflates `payload` back to its original `uncompressed_len` bytes.
+pub fn decompress(payload: &[u8], uncompressed_len: u32) -> Result<Vec<u8>, PicstegError> {
+    let mut decoder = DeflateDecoder::new(payload);
+    let mut decompressed = Vec::with_capacity(uncompressed_len as usize);
+
+    decoder
+        .read_to_end(&mut decompressed)
+        .map_err(PicstegError::Decompress)?;
+
+    Ok(decompressed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compress_and_decompress_round_trip() {
+        let payload = b"hello hello hello hello hello";
+        let compressed = compress(payload).unwrap();
+
+        assert_eq!(decompress(&compressed, payload.len() as u32).unwrap(), payload);
+    }
+
+    #[test]
+    fn compress_shrinks_repetitive_payloads() {
+        let payload = vec![b'a'; 1000];
+
+        assert!(compress(&payload).unwrap().len() < payload.len());
+    }
+}
@@ -0,0 +1,55 @@
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use sha2::{Digest, Sha256};
+
+use crate::error::PicstegError;
+
+const NONCE_SIZE: usize = 12;
+
+// Encrypts `payload` with AES-256-GCM under a key derived from `password`, returning the
+// random nonce followed by the ciphertext (including its authentication tag).
+pub fn encrypt(payload: &[u8], password: &str) -> Result<Vec<u8>, PicstegError> {
+    let cipher = Aes256Gcm::new(&derive_key(password));
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+
+    let ciphertext = cipher.encrypt(&nonce, payload).map_err(PicstegError::Encrypt)?;
+
+    Ok([nonce.as_slice(), &ciphertext].concat())
+}
+
+// Reverses encrypt(): splits the leading nonce off `payload` and authenticates/decrypts the rest.
+pub fn decrypt(payload: &[u8], password: &str) -> Result<Vec<u8>, PicstegError> {
+    if payload.len() < NONCE_SIZE {
+        return Err(PicstegError::Decrypt(aes_gcm::Error));
+    }
+
+    let (nonce, ciphertext) = payload.split_at(NONCE_SIZE);
+    let cipher = Aes256Gcm::new(&derive_key(password));
+
+    cipher
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(PicstegError::Decrypt)
+}
+
+fn derive_key(password: &str) -> Key<Aes256Gcm> {
+    *Key::<Aes256Gcm>::from_slice(&Sha256::digest(password.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encrypt_and_decrypt_round_trip() {
+        let ciphertext = encrypt(b"hi", "hunter2").unwrap();
+
+        assert_eq!(decrypt(&ciphertext, "hunter2").unwrap(), b"hi");
+    }
+
+    #[test]
+    fn decrypt_with_wrong_password_fails() {
+        let ciphertext = encrypt(b"hi", "hunter2").unwrap();
+
+        assert!(decrypt(&ciphertext, "wrong password").is_err());
+    }
+}
@@ -0,0 +1,72 @@
+use std::fmt;
+
+// Everything that can go wrong while encoding or decoding a secret, so callers get a typed
+// failure instead of a panic.
+#[derive(Debug)]
+pub enum PicstegError {
+    Io(std::io::Error),
+    ImageDecode(image::ImageError),
+    ImageSave(image::ImageError),
+    SecretTooLarge,
+    BadBitDepth,
+    ChecksumMismatch,
+    MissingPassword,
+    UnsupportedColorType,
+    BadOutputFormat,
+    Encrypt(aes_gcm::Error),
+    Decrypt(aes_gcm::Error),
+    Compress(std::io::Error),
+    Decompress(std::io::Error),
+}
+
+impl fmt::Display for PicstegError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PicstegError::Io(error) => write!(f, "I/O error: {}", error),
+            PicstegError::ImageDecode(error) => write!(f, "An error occured decoding the image: {}", error),
+            PicstegError::ImageSave(error) => write!(f, "Encoded image could not be saved: {}", error),
+            PicstegError::SecretTooLarge => write!(f, "The secret is too large to be encoded."),
+            PicstegError::BadBitDepth => write!(f, "Bits to encode must be between 1 and 8."),
+            PicstegError::ChecksumMismatch => write!(f, "Checksum failed: wrong bit depth or corrupted image."),
+            PicstegError::MissingPassword => {
+                write!(f, "Secret is encrypted; a password is required to decode it.")
+            }
+            PicstegError::UnsupportedColorType => write!(
+                f,
+                "Unsupported color type: only 8-bit grayscale, grayscale+alpha, RGB and RGBA images can be encoded."
+            ),
+            PicstegError::BadOutputFormat => write!(f, "Image must be saved with PNG format."),
+            PicstegError::Encrypt(error) => write!(f, "Failed to encrypt the secret: {}", error),
+            PicstegError::Decrypt(_) => {
+                write!(f, "Failed to decrypt the secret: wrong password or corrupted image.")
+            }
+            PicstegError::Compress(error) => write!(f, "Failed to compress the secret: {}", error),
+            PicstegError::Decompress(error) => write!(f, "Failed to decompress the secret: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for PicstegError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PicstegError::Io(error) => Some(error),
+            PicstegError::ImageDecode(error) | PicstegError::ImageSave(error) => Some(error),
+            PicstegError::Compress(error) | PicstegError::Decompress(error) => Some(error),
+            // aes_gcm::Error is deliberately opaque and doesn't implement std::error::Error.
+            PicstegError::Encrypt(_)
+            | PicstegError::Decrypt(_)
+            | PicstegError::SecretTooLarge
+            | PicstegError::BadBitDepth
+            | PicstegError::ChecksumMismatch
+            | PicstegError::MissingPassword
+            | PicstegError::UnsupportedColorType
+            | PicstegError::BadOutputFormat => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for PicstegError {
+    fn from(error: std::io::Error) -> Self {
+        PicstegError::Io(error)
+    }
+}
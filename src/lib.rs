@@ -0,0 +1,8 @@
+mod compress;
+mod crypto;
+mod error;
+mod scatter;
+mod utils;
+
+pub use error::PicstegError;
+pub use utils::{decode_image, encode_image, open_image, open_secret};
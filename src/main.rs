@@ -1,19 +1,15 @@
-mod utils;
-
 use std::fs::File;
 use std::io::Write;
+use std::process::ExitCode;
 
 use clap::{Parser, Subcommand};
-use image::RgbImage;
-use utils::{decode_image, encode_image, open_image, open_secret};
+use image::DynamicImage;
+use picsteg::{decode_image, encode_image, open_image, open_secret, PicstegError};
 
 #[derive(Parser)]
 #[clap(author, version, about, long_about = None)]
 #[clap(propagate_version = true)]
 struct Cli {
-    #[clap(short, long, help="Number of bits to encode or decode per byte. Default: 1")]
-    bits: Option<i8>,
-
     #[clap(subcommand)]
     command: Command,
 }
@@ -28,6 +24,12 @@ enum Command {
         secret: std::path::PathBuf,
         #[clap(short, long, help = "Output path of the encoded image")]
         output: std::path::PathBuf,
+        #[clap(short, long, help = "Number of bits to encode per channel. Default: 1")]
+        bits: Option<i8>,
+        #[clap(short, long, help = "Password used to encrypt the secret and scatter it across the image")]
+        password: Option<String>,
+        #[clap(short, long, help = "Deflate-compress the secret before encoding it")]
+        compress: bool,
     },
     #[clap(about = "Decode a secret from an image")]
     Decode {
@@ -35,49 +37,62 @@ enum Command {
         image: std::path::PathBuf,
         #[clap(short, long, help = "Output path of the decoded secret")]
         output: std::path::PathBuf,
+        #[clap(short, long, help = "Password the secret was encrypted and scattered with")]
+        password: Option<String>,
     },
 }
 
-fn main() {
+fn main() -> ExitCode {
     let cli = Cli::parse();
 
-    let bits: i8 = cli.bits.unwrap_or(1);
+    if let Err(error) = run(&cli) {
+        eprintln!("Error: {}", error);
+        return ExitCode::FAILURE;
+    }
+
+    ExitCode::SUCCESS
+}
 
+fn run(cli: &Cli) -> Result<(), PicstegError> {
     match &cli.command {
         Command::Encode {
             image,
             secret,
             output,
+            bits,
+            password,
+            compress,
         } => {
-            if output.extension().unwrap() != "png" {
-                panic!("Image must be saved with PNG format.");
+            if output.extension().and_then(|extension| extension.to_str()) != Some("png") {
+                return Err(PicstegError::BadOutputFormat);
             }
 
-            let mut image: RgbImage = open_image(image);
+            let image: DynamicImage = open_image(image)?;
+            let secret: Vec<u8> = open_secret(secret)?;
 
-            let secret: String = open_secret(secret);
+            let image = encode_image(image, secret, bits.unwrap_or(1), password.as_deref(), *compress)?;
 
-            image = encode_image(image, secret, bits);
-
-            if let Err(error) = image.save(output) {
-                panic!("Encoded image could not be saved: {}", error)
-            }
+            image.save(output).map_err(PicstegError::ImageSave)
         }
         Command::Decode {
             image,
             output,
+            password,
         } => {
-            let image: RgbImage = open_image(image);
-            let secret: String = decode_image(image, bits);
+            let image: DynamicImage = open_image(image)?;
+            let (secret, extension) = decode_image(image, password.as_deref())?;
 
-            match File::create(output) {
-                Ok(mut file) => {
-                    if let Err(error) = write!(file, "{}", secret) {
-                        panic!("Secret could not be written: {error}")
-                    }
+            let mut output = output.clone();
+            if output.extension().is_none() {
+                if let Some(extension) = extension {
+                    output.set_extension(extension);
                 }
-                Err(error) => panic!("Secret file could not be crated: {}", error),
             }
+
+            let mut file = File::create(&output)?;
+            file.write_all(&secret)?;
+
+            Ok(())
         }
     }
 }
@@ -0,0 +1,93 @@
+use sha2::{Digest, Sha256};
+
+// Returns the channel visiting order: identity when no password is set, otherwise a
+// deterministic Fisher-Yates shuffle of 0..n_channels seeded from the password's SHA-256 hash.
+pub fn channel_order(n_channels: usize, password: Option<&str>) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..n_channels).collect();
+
+    let password = match password {
+        Some(password) => password,
+        None => return order,
+    };
+
+    let mut rng = XorShift128::seeded(password);
+
+    for i in (1..order.len()).rev() {
+        let j = rng.below(i as u64 + 1) as usize;
+        order.swap(i, j);
+    }
+
+    order
+}
+
+struct XorShift128 {
+    state: [u64; 2],
+}
+
+impl XorShift128 {
+    fn seeded(password: &str) -> XorShift128 {
+        let digest = Sha256::digest(password.as_bytes());
+
+        let mut state = [
+            u64::from_le_bytes(digest[0..8].try_into().unwrap()),
+            u64::from_le_bytes(digest[8..16].try_into().unwrap()),
+        ];
+
+        if state == [0, 0] {
+            state[0] = 1;
+        }
+
+        XorShift128 { state }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut s1 = self.state[0];
+        let s0 = self.state[1];
+
+        self.state[0] = s0;
+        s1 ^= s1 << 23;
+        s1 ^= s1 >> 17;
+        s1 ^= s0 ^ (s0 >> 26);
+        self.state[1] = s1;
+
+        self.state[1].wrapping_add(s0)
+    }
+
+    fn below(&mut self, bound: u64) -> u64 {
+        self.next_u64() % bound
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identity_order_without_password() {
+        assert_eq!(channel_order(5, None), vec![0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn same_password_yields_same_order() {
+        assert_eq!(
+            channel_order(100, Some("hunter2")),
+            channel_order(100, Some("hunter2"))
+        );
+    }
+
+    #[test]
+    fn different_passwords_yield_different_orders() {
+        assert_ne!(
+            channel_order(100, Some("hunter2")),
+            channel_order(100, Some("correct horse"))
+        );
+    }
+
+    #[test]
+    fn order_is_a_permutation() {
+        let mut order = channel_order(200, Some("hunter2"));
+        order.sort();
+
+        assert_eq!(order, (0..200).collect::<Vec<usize>>());
+    }
+}
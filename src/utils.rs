@@ -1,124 +1,296 @@
 use std::fs;
 
-use image::{io::Reader, RgbImage};
+use image::{io::Reader, DynamicImage};
 
-pub const DELIMITER: &str = "#####";
+use crate::compress;
+use crate::crypto;
+use crate::error::PicstegError;
+use crate::scatter::channel_order;
 
-pub fn open_secret(path: &std::path::PathBuf) -> String {
-    match fs::read_to_string(path) {
-        Ok(secret) => secret,
-        Err(error) => panic!("Error opening the secret to encode: {}", error),
-    }
+const HEADER_SIZE: usize = 14;
+const ENCRYPTED_FLAG: u8 = 0b1000_0000;
+const COMPRESSED_FLAG: u8 = 0b0100_0000;
+
+pub fn open_secret(path: &std::path::PathBuf) -> Result<Vec<u8>, PicstegError> {
+    Ok(fs::read(path)?)
 }
 
-pub fn open_image(path: &std::path::PathBuf) -> RgbImage {
-    match Reader::open(path) {
-        Ok(file) => match file.decode() {
-            Ok(image) => image.into_rgb8(),
-            Err(error) => panic!("An error occured decoding the image: {:?}", error),
-        },
-        Err(error) => panic!("An error occured opening the image: {:?}", error),
-    }
+pub fn open_image(path: &std::path::PathBuf) -> Result<DynamicImage, PicstegError> {
+    Reader::open(path)?.decode().map_err(PicstegError::ImageDecode)
 }
 
-pub fn encode_image(mut image: RgbImage, secret: String, bits: i8) -> RgbImage {
-    if bits == 0 {
-        panic!("Bits to encode must be higher than 0.")
+pub fn encode_image(
+    mut image: DynamicImage,
+    secret: Vec<u8>,
+    bits: i8,
+    password: Option<&str>,
+    compress: bool,
+) -> Result<DynamicImage, PicstegError> {
+    if !(1..=8).contains(&bits) {
+        return Err(PicstegError::BadBitDepth);
     }
 
-    let mut secret_bits = text_to_bits(secret + DELIMITER);
+    let format = SecretFormat::detect(&secret);
+    let uncompressed_len = secret.len() as u32;
+
+    let secret = if compress { compress::compress(&secret)? } else { secret };
 
-    if !is_encodable(&image, &secret_bits, bits) {
-        panic!("The Secret is too large to be encoded.")
+    let (payload, encrypted) = match password {
+        Some(password) => (crypto::encrypt(&secret, password)?, true),
+        None => (secret, false),
+    };
+
+    if !is_encodable(&image, payload.len(), bits) {
+        return Err(PicstegError::SecretTooLarge);
     }
 
-    'encoding: for pixel in image.pixels_mut() {
-        for color in pixel.0.iter_mut() {
-            if secret_bits.is_empty() {
-                break 'encoding;
-            }
+    let header = build_header(
+        payload.len() as u32,
+        bits as u8,
+        format,
+        encrypted,
+        compress,
+        uncompressed_len,
+        crc32fast::hash(&payload),
+    );
 
-            let mut n_bits = bits as usize;
+    let header_bits = bytes_to_bits(&header);
+    let payload_bits = bytes_to_bits(&payload);
 
-            if n_bits > secret_bits.chars().count() {
-                n_bits = secret_bits.chars().count();
-            }
+    let channels = channels_mut(&mut image)?;
+    let order = channel_order(channels.len(), password);
 
-            let mut new_color = to_binary(*color)[..(8 - n_bits)].to_string();
+    let index = write_bits(channels, &order, 0, &header_bits, 1);
+    write_bits(channels, &order, index, &payload_bits, bits as usize);
 
-            new_color.push_str(&secret_bits[..n_bits]);
+    Ok(image)
+}
 
-            secret_bits = secret_bits[n_bits..].to_string();
+pub fn decode_image(
+    image: DynamicImage,
+    password: Option<&str>,
+) -> Result<(Vec<u8>, Option<&'static str>), PicstegError> {
+    let channels = image.as_bytes();
+    let order = channel_order(channels.len(), password);
 
-            *color = u8::from_str_radix(&new_color, 2).unwrap();
-        }
+    if order.len() < HEADER_SIZE * 8 {
+        return Err(PicstegError::ChecksumMismatch);
     }
 
-    image
-}
+    let (header_bits, index) = read_bits(channels, &order, 0, HEADER_SIZE * 8, 1);
+    let (length, bits, format, encrypted, compressed, uncompressed_len, crc) =
+        parse_header(&bits_to_bytes(&header_bits));
 
-pub fn decode_image(image: RgbImage, bits: i8) -> String {
-    if bits == 0 {
-        panic!("Bits to decode must be higher than 0.")
+    if !(1..=8).contains(&bits) {
+        return Err(PicstegError::ChecksumMismatch);
     }
 
-    let mut secret = String::from("");
-    let mut char = String::from("");
+    let payload_channels = ((length as usize * 8) as f64 / bits as f64).ceil() as usize;
+    if index + payload_channels > order.len() {
+        return Err(PicstegError::ChecksumMismatch);
+    }
 
-    'decoding: for pixel in image.pixels() {
-        for color in pixel.0.iter() {
-            if secret.ends_with(DELIMITER) {
-                break 'decoding;
-            }
+    let (payload_bits, _) = read_bits(channels, &order, index, length as usize * 8, bits as usize);
+    let payload = bits_to_bytes(&payload_bits);
 
-            let mut n_bits = (8 - bits) as usize;
+    if crc32fast::hash(&payload) != crc {
+        return Err(PicstegError::ChecksumMismatch);
+    }
 
-            if secret.ends_with(&DELIMITER[..(DELIMITER.len() - 1)])
-                && (bits as usize) + char.len() >= 8
-            {
-                n_bits = char.len();
-            }
+    let secret = match (encrypted, password) {
+        (true, Some(password)) => crypto::decrypt(&payload, password)?,
+        (true, None) => return Err(PicstegError::MissingPassword),
+        (false, _) => payload,
+    };
 
-            char.push_str(&to_binary(*color)[n_bits..]);
+    let secret = if compressed {
+        compress::decompress(&secret, uncompressed_len)?
+    } else {
+        secret
+    };
 
-            if char.len() >= 8 {
-                secret.push(char::from_u32(u32::from_str_radix(&char[..8], 2).unwrap()).unwrap());
+    Ok((secret, format.extension()))
+}
 
-                char = String::from(&char[8..]);
-            }
-        }
+// Borrows the image's underlying pixel buffer as a flat, mutable channel array, regardless of
+// color type. Only the 8-bit color types are supported as encode targets.
+fn channels_mut(image: &mut DynamicImage) -> Result<&mut [u8], PicstegError> {
+    match image {
+        DynamicImage::ImageLuma8(buffer) => Ok(buffer),
+        DynamicImage::ImageLumaA8(buffer) => Ok(buffer),
+        DynamicImage::ImageRgb8(buffer) => Ok(buffer),
+        DynamicImage::ImageRgba8(buffer) => Ok(buffer),
+        _ => Err(PicstegError::UnsupportedColorType),
     }
+}
+
+// Writes `bits` into `channels` (visited via `order`) starting at `pos`, `n_bits` at a time; returns the next free position.
+fn write_bits(channels: &mut [u8], order: &[usize], mut pos: usize, bits: &str, n_bits: usize) -> usize {
+    let mut written = 0;
+
+    while written < bits.len() {
+        let take = n_bits.min(bits.len() - written);
+        let channel = order[pos];
 
-    if !secret.ends_with(DELIMITER) {
-        panic!("Use the same amount of encoding bits for decoding the Image Secret.")
+        let mut new_color = to_binary(channels[channel])[..(8 - take)].to_string();
+        new_color.push_str(&bits[written..written + take]);
+
+        channels[channel] = u8::from_str_radix(&new_color, 2).unwrap();
+
+        written += take;
+        pos += 1;
     }
 
-    secret.replace(DELIMITER, "")
+    pos
+}
+
+// Inverse of write_bits: reads `total_bits` worth of payload back out, `n_bits` at a time.
+fn read_bits(channels: &[u8], order: &[usize], mut pos: usize, total_bits: usize, n_bits: usize) -> (String, usize) {
+    let mut bits = String::from("");
+    let mut remaining = total_bits;
+
+    while remaining > 0 {
+        let take = n_bits.min(remaining);
+
+        bits.push_str(&to_binary(channels[order[pos]])[(8 - take)..]);
+
+        remaining -= take;
+        pos += 1;
+    }
+
+    (bits, pos)
 }
 
 fn to_binary(number: u8) -> String {
     format!("{:0>8}", format!("{:b}", number))
 }
 
-fn is_encodable(image: &RgbImage, secret: &str, bits: i8) -> bool {
-    let chunks = ((secret.chars().count() as f64 / bits as f64).ceil()) as i64;
-    let n_bytes = (image.pixels().len() * 3) as i64;
+fn bytes_to_bits(bytes: &[u8]) -> String {
+    let mut bits = String::from("");
 
-    if chunks > n_bytes {
-        return false;
+    for byte in bytes {
+        bits += &to_binary(*byte);
     }
 
-    true
+    bits
 }
 
-fn text_to_bits(text: String) -> String {
-    let mut bits = String::from("");
+fn bits_to_bytes(bits: &str) -> Vec<u8> {
+    bits.as_bytes()
+        .chunks(8)
+        .map(|chunk| u8::from_str_radix(std::str::from_utf8(chunk).unwrap(), 2).unwrap())
+        .collect()
+}
+
+// 4-byte big-endian payload length, 1-byte bits-per-channel, 1-byte format tag, 4-byte big-endian
+// uncompressed length, 4-byte big-endian CRC32 of the payload; always at 1 bit/channel. The format
+// tag packs the detected SecretFormat in its low bits, the encrypted flag in its top bit, and the
+// compressed flag in the next bit down.
+fn build_header(
+    length: u32,
+    bits: u8,
+    format: SecretFormat,
+    encrypted: bool,
+    compressed: bool,
+    uncompressed_len: u32,
+    crc: u32,
+) -> [u8; HEADER_SIZE] {
+    let len = length.to_be_bytes();
+    let tag = format.tag() | if encrypted { ENCRYPTED_FLAG } else { 0 } | if compressed { COMPRESSED_FLAG } else { 0 };
+    let uncompressed_len = uncompressed_len.to_be_bytes();
+    let crc = crc.to_be_bytes();
+
+    [
+        len[0],
+        len[1],
+        len[2],
+        len[3],
+        bits,
+        tag,
+        uncompressed_len[0],
+        uncompressed_len[1],
+        uncompressed_len[2],
+        uncompressed_len[3],
+        crc[0],
+        crc[1],
+        crc[2],
+        crc[3],
+    ]
+}
+
+fn parse_header(header: &[u8]) -> (u32, u8, SecretFormat, bool, bool, u32, u32) {
+    let length = u32::from_be_bytes([header[0], header[1], header[2], header[3]]);
+    let tag = header[5];
+    let uncompressed_len = u32::from_be_bytes([header[6], header[7], header[8], header[9]]);
+    let crc = u32::from_be_bytes([header[10], header[11], header[12], header[13]]);
+
+    (
+        length,
+        header[4],
+        SecretFormat::from_tag(tag & !(ENCRYPTED_FLAG | COMPRESSED_FLAG)),
+        tag & ENCRYPTED_FLAG != 0,
+        tag & COMPRESSED_FLAG != 0,
+        uncompressed_len,
+        crc,
+    )
+}
+
+fn is_encodable(image: &DynamicImage, payload_len: usize, bits: i8) -> bool {
+    let header_channels = HEADER_SIZE * 8;
+    let payload_channels = ((payload_len * 8) as f64 / bits as f64).ceil() as usize;
+    let n_channels = (image.width() * image.height()) as usize * image.color().channel_count() as usize;
 
-    for byte in text.into_bytes() {
-        bits += &to_binary(byte);
+    header_channels + payload_channels <= n_channels
+}
+
+// Recognized secret file types, sniffed from the leading bytes so Decode can restore a sensible extension.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum SecretFormat {
+    Unknown,
+    Png,
+    Jpeg,
+    Gif,
+}
+
+impl SecretFormat {
+    fn detect(payload: &[u8]) -> SecretFormat {
+        if payload.starts_with(&[0x89, b'P', b'N', b'G']) {
+            SecretFormat::Png
+        } else if payload.starts_with(&[0xFF, 0xD8, 0xFF]) {
+            SecretFormat::Jpeg
+        } else if payload.starts_with(b"GIF8") {
+            SecretFormat::Gif
+        } else {
+            SecretFormat::Unknown
+        }
     }
 
-    bits
+    fn tag(self) -> u8 {
+        match self {
+            SecretFormat::Unknown => 0,
+            SecretFormat::Png => 1,
+            SecretFormat::Jpeg => 2,
+            SecretFormat::Gif => 3,
+        }
+    }
+
+    fn from_tag(tag: u8) -> SecretFormat {
+        match tag {
+            1 => SecretFormat::Png,
+            2 => SecretFormat::Jpeg,
+            3 => SecretFormat::Gif,
+            _ => SecretFormat::Unknown,
+        }
+    }
+
+    fn extension(self) -> Option<&'static str> {
+        match self {
+            SecretFormat::Png => Some("png"),
+            SecretFormat::Jpeg => Some("jpg"),
+            SecretFormat::Gif => Some("gif"),
+            SecretFormat::Unknown => None,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -127,32 +299,26 @@ pub mod tests {
 
     use super::*;
 
-    use image::Rgb;
+    use image::{GrayAlphaImage, GrayImage, Luma, LumaA, Rgb, Rgba, RgbImage, RgbaImage};
     use std::io::Write;
     use tempdir::TempDir;
 
     #[test]
-    #[should_panic(
-        expected = "An error occured opening the image: Os { code: 2, kind: NotFound, message: \"No such file or directory\" }"
-    )]
     fn open_image_file() {
         let tmp_dir = TempDir::new("tmp").unwrap();
         let image_path = tmp_dir.path().join("image.png");
 
-        open_image(&image_path);
+        assert!(matches!(open_image(&image_path), Err(PicstegError::Io(_))));
     }
 
     #[test]
-    #[should_panic(
-        expected = "An error occured decoding the image: Unsupported(UnsupportedError { format: Unknown, kind: Format(Unknown) })"
-    )]
     fn error_open_image_file() {
         let tmp_dir = TempDir::new("tmp").unwrap();
         let secret_path = tmp_dir.path().join("secret.txt");
 
         File::create(&secret_path).unwrap();
 
-        open_image(&secret_path);
+        assert!(matches!(open_image(&secret_path), Err(PicstegError::ImageDecode(_))));
     }
 
     #[test]
@@ -163,25 +329,15 @@ pub mod tests {
 
         writeln!(tmp_file, "This is a secret.").unwrap();
 
-        assert_eq!(open_secret(&secret_path), "This is a secret.\n");
+        assert_eq!(open_secret(&secret_path).unwrap(), b"This is a secret.\n");
     }
 
     #[test]
-    #[should_panic(
-        expected = "Error opening the secret to encode: No such file or directory (os error 2)"
-    )]
     fn error_open_secret_file() {
         let tmp_dir = TempDir::new("tmp").unwrap();
         let secret_path = tmp_dir.path().join("secret.txt");
 
-        open_secret(&secret_path);
-    }
-
-    #[test]
-    fn string_to_bits() {
-        assert_eq!(text_to_bits(String::from("Hi")), "0100100001101001");
-        assert_eq!(text_to_bits(String::from("30")), "0011001100110000");
-        assert_eq!(text_to_bits(String::from("@#")), "0100000000100011");
+        assert!(matches!(open_secret(&secret_path), Err(PicstegError::Io(_))));
     }
 
     #[test]
@@ -190,40 +346,46 @@ pub mod tests {
     }
 
     #[test]
-    #[should_panic(expected = "Bits to decode must be higher than 0.")]
-    fn decode_minimum_bits_on_each_color() {
-        decode_image(mock_image(), 0);
+    fn encode_minimum_bits_on_each_color() {
+        assert!(matches!(
+            encode_image(mock_image(), b"hi".to_vec(), 0, None, false),
+            Err(PicstegError::BadBitDepth)
+        ));
     }
 
     #[test]
-    #[should_panic(expected = "Bits to encode must be higher than 0.")]
-    fn encode_minimum_bits_on_each_color() {
-        encode_image(mock_image(), text_to_bits(String::from("hi")), 0);
+    fn encode_rejects_bits_above_eight() {
+        assert!(matches!(
+            encode_image(mock_image(), b"hi".to_vec(), 9, None, false),
+            Err(PicstegError::BadBitDepth)
+        ));
     }
 
     #[test]
     fn decode_image_secret() {
-        assert_eq!(decode_image(encoded_image(), 6), String::from("hi"));
-    }
+        let (secret, extension) = decode_image(encoded_image(), None).unwrap();
 
-    #[test]
-    #[should_panic(
-        expected = "Use the same amount of encoding bits for decoding the Image Secret."
-    )]
-    fn error_decode_image_secret() {
-        assert_eq!(decode_image(encoded_image(), 7), String::from("hi"));
+        assert_eq!(secret, b"hi");
+        assert_eq!(extension, None);
     }
 
     #[test]
-    #[should_panic(expected = "The Secret is too large to be encoded.")]
     fn error_to_encode_large_secret_into_picture() {
-        encode_image(mock_image(), String::from("Heyo"), 1);
+        let result = encode_image(
+            mock_image(),
+            b"This secret will never fit in here.".to_vec(),
+            1,
+            None,
+            false,
+        );
+
+        assert!(matches!(result, Err(PicstegError::SecretTooLarge)));
     }
 
     #[test]
     fn encode_image_secret() {
         assert_eq!(
-            encode_image(mock_image(), String::from("hi"), 6),
+            encode_image(mock_image(), b"hi".to_vec(), 6, None, false).unwrap(),
             encoded_image()
         );
     }
@@ -231,56 +393,243 @@ pub mod tests {
     #[test]
     fn encode_and_decode() {
         for i in 1..9 {
-            assert_eq!(
-                decode_image(encode_image(rand_image(), String::from("hi"), i), i),
-                String::from("hi")
-            );
+            let (secret, _) = decode_image(encode_image(rand_image(), b"hi".to_vec(), i, None, false).unwrap(), None).unwrap();
+
+            assert_eq!(secret, b"hi");
         }
     }
 
-    fn rand_image() -> RgbImage {
-        let width: u32 = 5;
-        let height: u32 = 4;
+    #[test]
+    fn encode_and_decode_detects_known_secret_format() {
+        let png_bytes = vec![0x89, b'P', b'N', b'G', 1, 2, 3, 4];
+        let (secret, extension) =
+            decode_image(encode_image(rand_image(), png_bytes.clone(), 4, None, false).unwrap(), None).unwrap();
 
-        let mut img = RgbImage::new(width, height);
+        assert_eq!(secret, png_bytes);
+        assert_eq!(extension, Some("png"));
+    }
 
-        for w in 0..width {
-            for h in 0..height {
-                img.put_pixel(w, h, Rgb([225, 104, 175]));
-            }
+    #[test]
+    fn encode_and_decode_with_password() {
+        for i in 1..9 {
+            let encoded = encode_image(rand_image(), b"hi".to_vec(), i, Some("hunter2"), false).unwrap();
+            let (secret, _) = decode_image(encoded, Some("hunter2")).unwrap();
+
+            assert_eq!(secret, b"hi");
+        }
+    }
+
+    #[test]
+    fn decode_with_wrong_password_fails() {
+        let encoded = encode_image(rand_image(), b"hi".to_vec(), 4, Some("hunter2"), false).unwrap();
+
+        assert!(decode_image(encoded, Some("wrong password")).is_err());
+    }
+
+    #[test]
+    fn decode_encrypted_secret_without_password_fails() {
+        let encoded = encode_image(rand_image(), b"hi".to_vec(), 4, Some("hunter2"), false).unwrap();
+
+        assert!(decode_image(encoded, None).is_err());
+    }
+
+    #[test]
+    fn decode_corrupted_image_fails_checksum() {
+        let mut encoded = encode_image(rand_image(), b"hi".to_vec(), 4, None, false).unwrap();
+
+        let channels = channels_mut(&mut encoded).unwrap();
+        channels[HEADER_SIZE * 8] ^= 0xFF;
+
+        assert!(matches!(decode_image(encoded, None), Err(PicstegError::ChecksumMismatch)));
+    }
+
+    #[test]
+    fn decode_image_too_small_for_header_fails() {
+        let image = DynamicImage::ImageRgb8(RgbImage::from_pixel(6, 6, Rgb([225, 104, 175])));
+
+        assert!(matches!(decode_image(image, None), Err(PicstegError::ChecksumMismatch)));
+    }
+
+    #[test]
+    fn encode_and_decode_with_compression() {
+        let secret = vec![b'a'; 200];
+
+        for i in 1..9 {
+            let (decoded, _) =
+                decode_image(encode_image(rand_image(), secret.clone(), i, None, true).unwrap(), None).unwrap();
+
+            assert_eq!(decoded, secret);
         }
+    }
+
+    #[test]
+    fn compression_raises_effective_capacity() {
+        let secret = vec![b'a'; 200];
 
-        return img;
+        assert!(encode_image(rand_image(), secret, 1, None, true).is_ok());
     }
 
-    fn mock_image() -> RgbImage {
-        let mut img = RgbImage::new(2, 3);
+    #[test]
+    fn encode_and_decode_grayscale_image() {
+        let image = DynamicImage::ImageLuma8(GrayImage::from_pixel(12, 12, Luma([104])));
+        let (secret, _) = decode_image(encode_image(image, b"hi".to_vec(), 4, None, false).unwrap(), None).unwrap();
+
+        assert_eq!(secret, b"hi");
+    }
 
-        img.put_pixel(0, 0, Rgb([225, 12, 99]));
-        img.put_pixel(1, 0, Rgb([155, 2, 50]));
+    #[test]
+    fn encode_and_decode_grayscale_alpha_image() {
+        let image = DynamicImage::ImageLumaA8(GrayAlphaImage::from_pixel(12, 12, LumaA([104, 255])));
+        let (secret, _) = decode_image(encode_image(image, b"hi".to_vec(), 4, None, false).unwrap(), None).unwrap();
 
-        img.put_pixel(0, 1, Rgb([99, 51, 15]));
-        img.put_pixel(1, 1, Rgb([15, 55, 22]));
+        assert_eq!(secret, b"hi");
+    }
 
-        img.put_pixel(0, 2, Rgb([155, 61, 87]));
-        img.put_pixel(1, 2, Rgb([63, 30, 17]));
+    #[test]
+    fn encode_and_decode_rgba_image() {
+        let image = DynamicImage::ImageRgba8(RgbaImage::from_pixel(12, 12, Rgba([225, 104, 175, 255])));
+        let (secret, _) = decode_image(encode_image(image, b"hi".to_vec(), 4, None, false).unwrap(), None).unwrap();
 
-        return img;
+        assert_eq!(secret, b"hi");
     }
 
-    //Image Encoded with "hi"
-    fn encoded_image() -> RgbImage {
-        let mut img = RgbImage::new(2, 3);
+    #[test]
+    fn encode_rejects_unsupported_color_type() {
+        let image = DynamicImage::ImageRgb16(image::ImageBuffer::from_pixel(12, 12, image::Rgb([225u16, 104, 175])));
 
-        img.put_pixel(0, 0, Rgb([218, 6, 100]));
-        img.put_pixel(1, 0, Rgb([163, 8, 50]));
+        assert!(matches!(
+            encode_image(image, b"hi".to_vec(), 4, None, false),
+            Err(PicstegError::UnsupportedColorType)
+        ));
+    }
 
-        img.put_pixel(0, 1, Rgb([76, 35, 8]));
-        img.put_pixel(1, 1, Rgb([15, 55, 22]));
+    #[test]
+    fn rgba_image_has_more_capacity_than_rgb_image() {
+        // The same secret fits at bits=1 in an RGBA image but not in an equally sized RGB one,
+        // because the alpha channel is now also used to carry payload bits.
+        let secret = [b'a'; 50];
 
-        img.put_pixel(0, 2, Rgb([155, 61, 87]));
-        img.put_pixel(1, 2, Rgb([63, 30, 17]));
+        let rgb = DynamicImage::ImageRgb8(RgbImage::from_pixel(12, 12, Rgb([225, 104, 175])));
+        let rgba = DynamicImage::ImageRgba8(RgbaImage::from_pixel(12, 12, Rgba([225, 104, 175, 255])));
+
+        assert!(!is_encodable(&rgb, secret.len(), 1));
+        assert!(is_encodable(&rgba, secret.len(), 1));
+    }
+
+    fn rand_image() -> DynamicImage {
+        let width: u32 = 12;
+        let height: u32 = 12;
+
+        let mut img = RgbImage::new(width, height);
+
+        for w in 0..width {
+            for h in 0..height {
+                img.put_pixel(w, h, Rgb([225, 104, 175]));
+            }
+        }
+
+        DynamicImage::ImageRgb8(img)
+    }
+
+    // A 10x4 image gives enough channels (120) to hold the header (112) plus a short payload.
+    fn mock_image() -> DynamicImage {
+        let mut img = RgbImage::new(10, 4);
+
+        img.put_pixel(0, 0, Rgb([0, 50, 100]));
+        img.put_pixel(1, 0, Rgb([7, 57, 107]));
+        img.put_pixel(2, 0, Rgb([14, 64, 114]));
+        img.put_pixel(3, 0, Rgb([21, 71, 121]));
+        img.put_pixel(4, 0, Rgb([28, 78, 128]));
+        img.put_pixel(5, 0, Rgb([35, 85, 135]));
+        img.put_pixel(6, 0, Rgb([42, 92, 142]));
+        img.put_pixel(7, 0, Rgb([49, 99, 149]));
+        img.put_pixel(8, 0, Rgb([56, 106, 156]));
+        img.put_pixel(9, 0, Rgb([63, 113, 163]));
+
+        img.put_pixel(0, 1, Rgb([70, 120, 170]));
+        img.put_pixel(1, 1, Rgb([77, 127, 177]));
+        img.put_pixel(2, 1, Rgb([84, 134, 184]));
+        img.put_pixel(3, 1, Rgb([91, 141, 191]));
+        img.put_pixel(4, 1, Rgb([98, 148, 198]));
+        img.put_pixel(5, 1, Rgb([105, 155, 205]));
+        img.put_pixel(6, 1, Rgb([112, 162, 212]));
+        img.put_pixel(7, 1, Rgb([119, 169, 219]));
+        img.put_pixel(8, 1, Rgb([126, 176, 226]));
+        img.put_pixel(9, 1, Rgb([133, 183, 233]));
+
+        img.put_pixel(0, 2, Rgb([140, 190, 240]));
+        img.put_pixel(1, 2, Rgb([147, 197, 247]));
+        img.put_pixel(2, 2, Rgb([154, 204, 254]));
+        img.put_pixel(3, 2, Rgb([161, 211, 5]));
+        img.put_pixel(4, 2, Rgb([168, 218, 12]));
+        img.put_pixel(5, 2, Rgb([175, 225, 19]));
+        img.put_pixel(6, 2, Rgb([182, 232, 26]));
+        img.put_pixel(7, 2, Rgb([189, 239, 33]));
+        img.put_pixel(8, 2, Rgb([196, 246, 40]));
+        img.put_pixel(9, 2, Rgb([203, 253, 47]));
+
+        img.put_pixel(0, 3, Rgb([210, 4, 54]));
+        img.put_pixel(1, 3, Rgb([217, 11, 61]));
+        img.put_pixel(2, 3, Rgb([224, 18, 68]));
+        img.put_pixel(3, 3, Rgb([231, 25, 75]));
+        img.put_pixel(4, 3, Rgb([238, 32, 82]));
+        img.put_pixel(5, 3, Rgb([245, 39, 89]));
+        img.put_pixel(6, 3, Rgb([252, 46, 96]));
+        img.put_pixel(7, 3, Rgb([3, 53, 103]));
+        img.put_pixel(8, 3, Rgb([10, 60, 110]));
+        img.put_pixel(9, 3, Rgb([17, 67, 117]));
+
+        DynamicImage::ImageRgb8(img)
+    }
 
-        return img;
+    // mock_image() encoded with the secret "hi" at 6 bits per channel.
+    fn encoded_image() -> DynamicImage {
+        let mut img = RgbImage::new(10, 4);
+
+        img.put_pixel(0, 0, Rgb([0, 50, 100]));
+        img.put_pixel(1, 0, Rgb([6, 56, 106]));
+        img.put_pixel(2, 0, Rgb([14, 64, 114]));
+        img.put_pixel(3, 0, Rgb([20, 70, 120]));
+        img.put_pixel(4, 0, Rgb([28, 78, 128]));
+        img.put_pixel(5, 0, Rgb([34, 84, 134]));
+        img.put_pixel(6, 0, Rgb([42, 92, 142]));
+        img.put_pixel(7, 0, Rgb([48, 98, 148]));
+        img.put_pixel(8, 0, Rgb([56, 106, 156]));
+        img.put_pixel(9, 0, Rgb([62, 112, 162]));
+
+        img.put_pixel(0, 1, Rgb([71, 120, 170]));
+        img.put_pixel(1, 1, Rgb([76, 126, 176]));
+        img.put_pixel(2, 1, Rgb([84, 135, 185]));
+        img.put_pixel(3, 1, Rgb([90, 140, 190]));
+        img.put_pixel(4, 1, Rgb([98, 148, 198]));
+        img.put_pixel(5, 1, Rgb([104, 154, 204]));
+        img.put_pixel(6, 1, Rgb([112, 162, 212]));
+        img.put_pixel(7, 1, Rgb([118, 168, 218]));
+        img.put_pixel(8, 1, Rgb([126, 176, 226]));
+        img.put_pixel(9, 1, Rgb([132, 182, 232]));
+
+        img.put_pixel(0, 2, Rgb([140, 190, 240]));
+        img.put_pixel(1, 2, Rgb([146, 196, 246]));
+        img.put_pixel(2, 2, Rgb([154, 204, 254]));
+        img.put_pixel(3, 2, Rgb([160, 210, 4]));
+        img.put_pixel(4, 2, Rgb([168, 218, 12]));
+        img.put_pixel(5, 2, Rgb([174, 224, 18]));
+        img.put_pixel(6, 2, Rgb([183, 232, 27]));
+        img.put_pixel(7, 2, Rgb([189, 238, 33]));
+        img.put_pixel(8, 2, Rgb([197, 246, 40]));
+        img.put_pixel(9, 2, Rgb([202, 253, 46]));
+
+        img.put_pixel(0, 3, Rgb([210, 5, 54]));
+        img.put_pixel(1, 3, Rgb([216, 11, 61]));
+        img.put_pixel(2, 3, Rgb([224, 18, 69]));
+        img.put_pixel(3, 3, Rgb([230, 25, 74]));
+        img.put_pixel(4, 3, Rgb([239, 32, 83]));
+        img.put_pixel(5, 3, Rgb([244, 39, 88]));
+        img.put_pixel(6, 3, Rgb([253, 47, 96]));
+        img.put_pixel(7, 3, Rgb([2, 26, 70]));
+        img.put_pixel(8, 3, Rgb([9, 60, 110]));
+        img.put_pixel(9, 3, Rgb([17, 67, 117]));
+
+        DynamicImage::ImageRgb8(img)
     }
 }